@@ -0,0 +1,3 @@
+//! Deserialization support.
+
+pub mod read;
@@ -1,6 +1,97 @@
+use core::cmp;
 use error::Result;
 use serde;
-use std::{io, slice};
+
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// A minimal byte source the deserializer reads through.
+///
+/// `BincodeRead` used to require `std::io::Read` as a supertrait, which
+/// hard-bound the whole deserializer to `std`. This trait is the in-crate
+/// shim that breaks that dependency, the same way the embedded and Bitcoin
+/// ecosystems define their own `io` abstraction: a `read`/`read_exact` pair
+/// over `&mut [u8]` reporting failures through the crate's own [`Result`].
+///
+/// Under the default `std` feature every `std::io::Read` implements this
+/// automatically through the blanket impl below, so existing users are
+/// unaffected. Under `no_std` + `alloc` a caller supplies their own
+/// implementor instead.
+pub trait Read {
+    /// Pull some bytes from this source into `buf`, returning the number of
+    /// bytes read. Mirrors `std::io::Read::read`.
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+    /// Read exactly enough bytes to fill `buf`, returning an unexpected-EOF
+    /// error if the source runs dry first. Mirrors `std::io::Read::read_exact`.
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()>;
+}
+
+#[cfg(feature = "std")]
+impl<R: io::Read> Read for R {
+    #[inline(always)]
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        io::Read::read(self, buf).map_err(|e| Box::new(::ErrorKind::Io(e)))
+    }
+
+    #[inline(always)]
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        io::Read::read_exact(self, buf).map_err(|e| Box::new(::ErrorKind::Io(e)))
+    }
+}
+
+/// The largest number of bytes by which `IoReader` extends its scratch buffer
+/// in a single step while filling it. A length prefix decoded from the input
+/// is only ever honoured `DEFAULT_READ_CHUNK` bytes at a time, so a corrupt
+/// or hostile source declaring a huge length can no longer provoke an
+/// up-front multi-gigabyte allocation before `read_exact` observes the short
+/// stream.
+const DEFAULT_READ_CHUNK: usize = 8 * 1024;
+
+// `BincodeRead` is a public trait, but its methods traffic in the crate's
+// private `Result`/`ErrorKind`, so implementing it outside the crate is not
+// something we want to promise by default. Following the serde_cbor
+// `unsealed_read_write` pattern the trait carries a private supertrait bound
+// that only the built-in readers can satisfy, sealing it shut. Turning on the
+// `unsealed_read_write` feature blanket-impls the bound for everyone, opening
+// the trait to third-party reader backends (mmap windows, async-to-sync
+// bridges, decompressors) alongside the public error constructors below.
+#[cfg(not(feature = "unsealed_read_write"))]
+mod sealed {
+    pub trait Sealed {}
+    impl<'storage> Sealed for super::SliceReader<'storage> {}
+    impl<R> Sealed for super::IoReader<R> {}
+}
+
+#[cfg(feature = "unsealed_read_write")]
+mod sealed {
+    pub trait Sealed {}
+    impl<T: ?Sized> Sealed for T {}
+}
+
+/// Construct the "unexpected end of input" error the built-in readers return
+/// when a source runs dry mid-field, so an external [`BincodeRead`] implementor
+/// can report the same error. Only available with the `unsealed_read_write`
+/// feature.
+#[cfg(feature = "unsealed_read_write")]
+#[inline(always)]
+pub fn unexpected_eof() -> Box<::ErrorKind> {
+    SliceReader::unexpected_eof()
+}
+
+/// Construct the invalid-UTF-8 error the built-in readers return from
+/// `forward_read_str`, so an external [`BincodeRead`] implementor can report the
+/// same error. Only available with the `unsealed_read_write` feature.
+#[cfg(feature = "unsealed_read_write")]
+#[inline(always)]
+pub fn invalid_utf8(e: ::core::str::Utf8Error) -> Box<::ErrorKind> {
+    Box::new(::ErrorKind::InvalidUtf8Encoding(e))
+}
 
 /// An optional Read trait for advanced Bincode usage.
 ///
@@ -10,7 +101,10 @@ use std::{io, slice};
 /// The forward_read_* methods are necessary because some byte sources want
 /// to pass a long-lived borrow to the visitor and others want to pass a
 /// transient slice.
-pub trait BincodeRead<'storage>: io::Read {
+///
+/// This trait is sealed by default; enable the `unsealed_read_write` feature to
+/// implement it for your own byte source.
+pub trait BincodeRead<'storage>: Read + sealed::Sealed {
     /// Check that the next `length` bytes are a valid string and pass
     /// it on to the serde reader.
     fn forward_read_str<V>(&mut self, length: usize, visitor: V) -> Result<V::Value>
@@ -39,6 +133,7 @@ pub struct SliceReader<'storage> {
 pub struct IoReader<R> {
     reader: R,
     temp_buffer: Vec<u8>,
+    limit: Option<usize>,
 }
 
 impl<'storage> SliceReader<'storage> {
@@ -60,44 +155,121 @@ impl<'storage> SliceReader<'storage> {
 }
 
 impl<R> IoReader<R> {
-    /// Constructs an IoReadReader
+    /// Constructs an IoReadReader.
+    ///
+    /// Peak allocation is already bounded on this default path: `fill_buffer`
+    /// streams a length-prefixed field in `DEFAULT_READ_CHUNK`-sized steps and
+    /// grows the scratch buffer only as bytes actually arrive, so a corrupt
+    /// prefix can never provoke an up-front oversized reservation. The
+    /// additional reject-before-reserve guard is opt-in via [`with_limit`];
+    /// a reader built with `new` places no upper bound on a field's declared
+    /// length.
+    ///
+    /// [`with_limit`]: IoReader::with_limit
     pub fn new(r: R) -> IoReader<R> {
         IoReader {
             reader: r,
             temp_buffer: vec![],
+            limit: None,
+        }
+    }
+
+    /// Constructs an IoReadReader whose scratch buffer is pre-sized to
+    /// `capacity` bytes. Sizing the buffer once up front lets a stream of
+    /// length-prefixed fields be decoded without the buffer having to grow (and
+    /// reallocate) on the way up to its steady-state size.
+    pub fn with_capacity(r: R, capacity: usize) -> IoReader<R> {
+        IoReader {
+            reader: r,
+            temp_buffer: Vec::with_capacity(capacity),
+            limit: None,
+        }
+    }
+
+    /// Constructs an IoReadReader that refuses to buffer more than `limit`
+    /// bytes for a single length-prefixed field. A declared length above the
+    /// limit fails with `ErrorKind::SizeLimit` before any memory is reserved,
+    /// so an untrusted source can no longer drive the reader into an oversized
+    /// allocation; legitimate payloads up to `limit` are still streamed in
+    /// successfully.
+    pub fn with_limit(r: R, limit: usize) -> IoReader<R> {
+        IoReader {
+            reader: r,
+            temp_buffer: vec![],
+            limit: Some(limit),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl<'storage> io::Read for SliceReader<'storage> {
     #[inline(always)]
     fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
-        (&mut self.slice).read(out)
+        io::Read::read(&mut self.slice, out)
     }
     #[inline(always)]
     fn read_exact(&mut self, out: &mut [u8]) -> io::Result<()> {
-        (&mut self.slice).read_exact(out)
+        io::Read::read_exact(&mut self.slice, out)
     }
 }
 
-impl<R: io::Read> io::Read for IoReader<R> {
+// `IoReader` forwards the crate `Read` trait to its inner reader
+// unconditionally rather than going through an `io::Read` impl. Under `std`
+// the inner `R` reaches the crate trait via the blanket `impl<R: io::Read>
+// Read for R`, so bounding this impl on `R: Read` keeps the `BincodeRead for
+// IoReader` supertrait provable; routing through an `impl io::Read for
+// IoReader` instead would have required `R: io::Read` there and failed to
+// build the std deserializer. `IoReader` never implements `io::Read`, so this
+// impl does not overlap the blanket.
+impl<R: Read> Read for IoReader<R> {
     #[inline(always)]
-    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+    fn read(&mut self, out: &mut [u8]) -> Result<usize> {
         self.reader.read(out)
     }
     #[inline(always)]
-    fn read_exact(&mut self, out: &mut [u8]) -> io::Result<()> {
+    fn read_exact(&mut self, out: &mut [u8]) -> Result<()> {
         self.reader.read_exact(out)
     }
 }
 
+// Without `std` there is no `io::Read` to lean on for the `Read` supertrait,
+// so `SliceReader` implements the crate trait directly over its own backing
+// store.
+#[cfg(not(feature = "std"))]
+impl<'storage> Read for SliceReader<'storage> {
+    #[inline(always)]
+    fn read(&mut self, out: &mut [u8]) -> Result<usize> {
+        let length = cmp::min(out.len(), self.slice.len());
+        out[..length].copy_from_slice(&self.slice[..length]);
+        self.slice = &self.slice[length..];
+        Ok(length)
+    }
+
+    #[inline(always)]
+    fn read_exact(&mut self, out: &mut [u8]) -> Result<()> {
+        if out.len() > self.slice.len() {
+            return Err(SliceReader::unexpected_eof());
+        }
+        let (start, rest) = self.slice.split_at(out.len());
+        out.copy_from_slice(start);
+        self.slice = rest;
+        Ok(())
+    }
+}
+
 impl<'storage> SliceReader<'storage> {
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    fn unexpected_eof() -> Box<::ErrorKind> {
+        Box::new(::ErrorKind::Io(io::Error::new(io::ErrorKind::UnexpectedEof, "")))
+    }
+
+    // Without `std` there is no `io::Error` to wrap, so the error enum carries
+    // a dedicated `UnexpectedEof` variant in that configuration instead.
+    #[cfg(not(feature = "std"))]
     #[inline(always)]
     fn unexpected_eof() -> Box<::ErrorKind> {
-        return Box::new(::ErrorKind::Io(io::Error::new(
-            io::ErrorKind::UnexpectedEof,
-            "",
-        )));
+        Box::new(::ErrorKind::UnexpectedEof)
     }
 }
 
@@ -108,7 +280,7 @@ impl<'storage> BincodeRead<'storage> for SliceReader<'storage> {
         V: serde::de::Visitor<'storage>,
     {
         use ErrorKind;
-        let string = match ::std::str::from_utf8(self.get_byte_slice(length)?) {
+        let string = match ::core::str::from_utf8(self.get_byte_slice(length)?) {
             Ok(s) => s,
             Err(e) => return Err(ErrorKind::InvalidUtf8Encoding(e).into()),
         };
@@ -131,42 +303,52 @@ impl<'storage> BincodeRead<'storage> for SliceReader<'storage> {
 
 impl<R> IoReader<R>
 where
-    R: io::Read,
+    R: Read,
 {
     fn fill_buffer(&mut self, length: usize) -> Result<()> {
-        // We first reserve the space needed in our buffer.
-        let current_length = self.temp_buffer.len();
-        if length > current_length {
-            self.temp_buffer.reserve_exact(length - current_length);
+        // Reject an obviously oversized length before reserving anything, so a
+        // corrupt prefix can never translate into a huge allocation.
+        if let Some(limit) = self.limit {
+            if length > limit {
+                return Err(IoReader::<R>::size_limit());
+            }
         }
 
-        // Then create a slice with the length as our desired length. This is
-        // safe as long as we only write (no reads) to this buffer, because
-        // `reserve_exact` above has allocated this space.
-        let buf = unsafe {
-            slice::from_raw_parts_mut(self.temp_buffer.as_mut_ptr(), length)
-        };
-
-        // This method is assumed to properly handle slices which include
-        // uninitialized bytes (as ours does). See discussion at the link below.
-        // https://github.com/servo/bincode/issues/260
-        self.reader.read_exact(buf)?;
-
-        // Only after `read_exact` successfully returns do we set the buffer
-        // length. By doing this after the call to `read_exact`, we can avoid
-        // exposing uninitialized memory in the case of `read_exact` returning
-        // an error.
-        unsafe {
-            self.temp_buffer.set_len(length);
+        // `temp_buffer.len()` doubles as our initialized high-water mark. We
+        // grow it in capped chunks, zero-filling only the newly claimed tail,
+        // and only ever hand `read_exact` a slice that is already initialized.
+        // That retires the `from_raw_parts_mut`/`set_len` trick (issue #260):
+        // no uninitialized memory is ever exposed to a `Read` impl that might
+        // read its own destination, yet already-initialized bytes survive
+        // across calls so repeated small reads don't re-zero the buffer.
+        //
+        // The `BorrowedBuf`/`Read::read_buf` cursor expresses exactly this,
+        // but it is still unstable; the zero-fill path below is the
+        // portable fallback that behaves identically on older toolchains, at
+        // the cost of a one-time initialization of any freshly grown region.
+        let mut filled = 0;
+        while filled < length {
+            let chunk = cmp::min(length - filled, DEFAULT_READ_CHUNK);
+            let needed = filled + chunk;
+            if self.temp_buffer.len() < needed {
+                self.temp_buffer.resize(needed, 0);
+            }
+            self.reader.read_exact(&mut self.temp_buffer[filled..needed])?;
+            filled += chunk;
         }
 
         Ok(())
     }
+
+    #[inline(always)]
+    fn size_limit() -> Box<::ErrorKind> {
+        Box::new(::ErrorKind::SizeLimit)
+    }
 }
 
 impl<'a, R> BincodeRead<'a> for IoReader<R>
 where
-    R: io::Read,
+    R: Read,
 {
     fn forward_read_str<V>(&mut self, length: usize, visitor: V) -> Result<V::Value>
     where
@@ -174,7 +356,7 @@ where
     {
         self.fill_buffer(length)?;
 
-        let string = match ::std::str::from_utf8(&self.temp_buffer[..]) {
+        let string = match ::core::str::from_utf8(&self.temp_buffer[..length]) {
             Ok(s) => s,
             Err(e) => return Err(::ErrorKind::InvalidUtf8Encoding(e).into()),
         };
@@ -184,7 +366,12 @@ where
 
     fn get_byte_buffer(&mut self, length: usize) -> Result<Vec<u8>> {
         self.fill_buffer(length)?;
-        Ok(::std::mem::replace(&mut self.temp_buffer, Vec::new()))
+        // Copy the requested region into a fresh, caller-owned `Vec` but keep
+        // `temp_buffer` (and its capacity) in place. Handing the scratch buffer
+        // away with `mem::replace` forced a brand-new allocation on the next
+        // length-prefixed field, which is pathological for structs full of
+        // `Vec<u8>`/`String`; retaining it makes owned reads O(1) amortized.
+        Ok(self.temp_buffer[..length].to_vec())
     }
 
     fn forward_read_bytes<V>(&mut self, length: usize, visitor: V) -> Result<V::Value>
@@ -192,6 +379,6 @@ where
         V: serde::de::Visitor<'a>,
     {
         self.fill_buffer(length)?;
-        visitor.visit_bytes(&self.temp_buffer[..])
+        visitor.visit_bytes(&self.temp_buffer[..length])
     }
 }
@@ -0,0 +1,114 @@
+//! Errors that can be produced during (de)serialization.
+
+use core::fmt;
+
+#[cfg(feature = "std")]
+use std::error::Error as StdError;
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+
+use serde;
+
+/// The result of a serialization or deserialization operation.
+pub type Result<T> = ::core::result::Result<T, Error>;
+
+/// An error that can be produced during (de)serializing.
+pub type Error = Box<ErrorKind>;
+
+/// The kind of error that can be produced during a serialization or deserialization.
+#[derive(Debug)]
+pub enum ErrorKind {
+    /// If the error stems from the reader/writer that is being used
+    /// during (de)serialization, that error will be stored and returned here.
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    /// The reader reached the end of its input before a value was fully read.
+    ///
+    /// Under `std` an unexpected end of input is reported through the wrapped
+    /// [`ErrorKind::Io`] instead; without `std` there is no `io::Error` to
+    /// carry it, so this dedicated variant is used in that configuration.
+    #[cfg(not(feature = "std"))]
+    UnexpectedEof,
+    /// Returned if the deserializer attempts to deserialize a string that is not valid utf8
+    InvalidUtf8Encoding(::core::str::Utf8Error),
+    /// Returned if the deserializer attempts to deserialize a bool that was
+    /// not encoded as either a 1 or a 0
+    InvalidBoolEncoding(u8),
+    /// Returned if the deserializer attempts to deserialize a char that is not in the correct format.
+    InvalidCharEncoding,
+    /// Returned if the deserializer attempts to deserialize the tag of an enum that is
+    /// not in the expected ranges
+    InvalidTagEncoding(usize),
+    /// Serde has a deserialize_any method that lets the format hint to the
+    /// object which route to take in deserializing.
+    DeserializeAnyNotSupported,
+    /// If (de)serializing a message takes more than the provided size limit, this
+    /// error is returned.
+    SizeLimit,
+    /// Bincode can not encode sequences of unknown length (like iterators).
+    SequenceMustHaveLength,
+    /// A custom error message from Serde.
+    Custom(String),
+}
+
+#[cfg(feature = "std")]
+impl StdError for ErrorKind {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            ErrorKind::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        ErrorKind::Io(err).into()
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            #[cfg(feature = "std")]
+            ErrorKind::Io(ref ioerr) => write!(fmt, "io error: {}", ioerr),
+            #[cfg(not(feature = "std"))]
+            ErrorKind::UnexpectedEof => write!(fmt, "unexpected end of input"),
+            ErrorKind::InvalidUtf8Encoding(ref e) => write!(fmt, "string is not valid utf8: {}", e),
+            ErrorKind::InvalidBoolEncoding(b) => {
+                write!(fmt, "invalid u8 while decoding bool, expected 0 or 1, found {}", b)
+            }
+            ErrorKind::InvalidCharEncoding => write!(fmt, "char is not valid"),
+            ErrorKind::InvalidTagEncoding(tag) => {
+                write!(fmt, "tag for enum is not valid, found {}", tag)
+            }
+            ErrorKind::SequenceMustHaveLength => {
+                write!(fmt, "bincode can't encode infinite sequences")
+            }
+            ErrorKind::SizeLimit => write!(fmt, "the size limit has been reached"),
+            ErrorKind::DeserializeAnyNotSupported => write!(
+                fmt,
+                "bincode does not support the serde::Deserializer::deserialize_any method"
+            ),
+            ErrorKind::Custom(ref s) => s.fmt(fmt),
+        }
+    }
+}
+
+impl serde::de::Error for Error {
+    fn custom<T: fmt::Display>(desc: T) -> Error {
+        ErrorKind::Custom(desc.to_string()).into()
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        ErrorKind::Custom(msg.to_string()).into()
+    }
+}
@@ -0,0 +1,34 @@
+//! Bincode is a crate for encoding and decoding using a tiny binary
+//! serialization strategy.  Using it, you can easily go from having
+//! an object in memory, quickly serialize it to bytes, and then
+//! deserialize it back just as fast!
+//!
+//! ## Features
+//!
+//! By default bincode builds against `std`. Disabling the default features and
+//! enabling `alloc` builds the crate as `#![no_std]`: `SliceReader`
+//! deserialization from `&[u8]` keeps working, and `IoReader` wraps any
+//! implementor of the in-crate [`de::read::Read`] trait. The
+//! `unsealed_read_write` feature additionally opens [`de::read::BincodeRead`]
+//! up for third-party reader backends.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![crate_name = "bincode"]
+#![crate_type = "rlib"]
+#![crate_type = "dylib"]
+
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+
+// Under `std` (edition 2015) the `core` crate has to be named explicitly to
+// reach `core::*`; under `no_std` the compiler already injects
+// `extern crate core` at the crate root.
+#[cfg(feature = "std")]
+extern crate core;
+extern crate serde;
+
+pub mod de;
+mod error;
+
+pub use error::{Error, ErrorKind, Result};